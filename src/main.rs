@@ -1,39 +1,70 @@
 //! Renders a 2D scene containing a single, moving sprite.
 
+mod network;
+mod scene;
+
 use bevy::{
     math::NormedVectorSpace,
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
 
-use rand::random;
+use rand::{rngs::StdRng, SeedableRng};
 
-const UNIVERSE_WIDTH: f32 = 200.; // meters
-const UNIVERSE_HEIGHT: f32 = 200.; // meters
-const WINDOW_WIDTH: f32 = 800.; // pixels
-const WINDOW_HEIGHT: f32 = 400.; // pixels
+pub(crate) const UNIVERSE_WIDTH: f32 = 200.; // meters
+pub(crate) const UNIVERSE_HEIGHT: f32 = 200.; // meters
+pub(crate) const WINDOW_WIDTH: f32 = 800.; // pixels
+pub(crate) const WINDOW_HEIGHT: f32 = 400.; // pixels
 const SCALE: f32 = 2.; // ratio pixels/meter
-const G: f32 = -9.81;
-const FRICTION: f32 = 0.5;
+pub(crate) const G: f32 = -9.81;
+pub(crate) const FRICTION: f32 = 0.5;
+const THETA: f32 = 0.5; // Barnes-Hut opening angle
+const MIN_HALF_SIZE: f32 = 1e-3; // meters; below this, quadtree nodes stop subdividing
+
+const R: f32 = 50.; // flocking perception radius (meters)
+const R_SEP: f32 = 15.; // separation radius (meters)
+const SEP_WEIGHT: f32 = 1.0;
+const ALIGN_WEIGHT: f32 = 0.5;
+const COH_WEIGHT: f32 = 0.3;
+
+const RESTITUTION: f32 = 0.8; // collision elasticity, 0 = inelastic, 1 = perfectly elastic
+
+const SEED: u64 = 42; // default RNG seed, for reproducible runs
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
         .insert_resource(Time::<Fixed>::from_hz(60.0))
         .insert_resource(GameState::Paused)
         .insert_resource(TimeProgress::default())
+        .insert_resource(SimMode::default())
+        .insert_resource(SimRng::default())
+        .insert_resource(scene::PhysicsParams::default())
+        .insert_resource(scene::SceneState::default())
+        .init_asset::<scene::Universe>()
+        .init_asset_loader::<scene::UniverseLoader>()
         .add_systems(Startup, setup)
-        .add_systems(Update, time_progress)
-        .add_systems(
-            Update,
-            update_balls
-                .run_if(resource_exists::<GameState>.and_then(resource_equals(GameState::Running))),
-        )
-        .run();
+        .add_systems(Update, (time_progress, scene::apply_scene));
+
+    // A networked session advances physics from the GGRS rollback schedule
+    // instead; otherwise fall back to the local single-process demo.
+    match network::NetArgs::from_env() {
+        Some(net_args) => network::plugin(&mut app, net_args),
+        None => {
+            app.add_systems(
+                FixedUpdate,
+                (update_balls, resolve_collisions).chain().run_if(
+                    resource_exists::<GameState>.and_then(resource_equals(GameState::Running)),
+                ),
+            );
+        }
+    }
+
+    app.run();
 }
 
 #[derive(Resource, Debug, Clone, PartialEq)]
-enum GameState {
+pub(crate) enum GameState {
     Running,
     Paused,
 }
@@ -45,8 +76,54 @@ struct TimeProgress {
     frame_forward: u32,
 }
 
+/// Seedable RNG driving ball spawning, so a given seed always produces the
+/// same initial universe.
+#[derive(Resource)]
+pub(crate) struct SimRng(StdRng);
+
+impl Default for SimRng {
+    fn default() -> Self {
+        SimRng(StdRng::seed_from_u64(SEED))
+    }
+}
+
+impl std::ops::Deref for SimRng {
+    type Target = StdRng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for SimRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Which forces `update_balls` applies each frame: plain gravity, boids-style
+/// flocking, or both combined.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SimMode {
+    #[default]
+    Gravity,
+    Flocking,
+    Both,
+}
+
+impl SimMode {
+    /// Cycles Gravity -> Flocking -> Both -> Gravity.
+    fn next(self) -> Self {
+        match self {
+            SimMode::Gravity => SimMode::Flocking,
+            SimMode::Flocking => SimMode::Both,
+            SimMode::Both => SimMode::Gravity,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone)]
-struct Ball {
+pub(crate) struct Ball {
     position: Vec3,
     speed: Vec3,
     acceleration: Vec3,
@@ -69,20 +146,27 @@ impl Default for Ball {
 }
 
 impl Ball {
-    pub fn new(position: Vec3, speed: Vec3, acceleration: Vec3, mass: f32, size: f32) -> Self {
+    pub fn new(
+        position: Vec3,
+        speed: Vec3,
+        acceleration: Vec3,
+        mass: f32,
+        size: f32,
+        fixed: bool,
+    ) -> Self {
         Self {
             position,
             speed,
             acceleration,
             mass,
             size,
-            ..Default::default()
+            fixed,
         }
     }
 }
 
 #[derive(Bundle, Default)]
-struct BallBundle {
+pub(crate) struct BallBundle {
     ball: Ball,
     mesh: MaterialMesh2dBundle<ColorMaterial>,
 }
@@ -94,14 +178,16 @@ impl BallBundle {
         acceleration: Vec3,
         mass: f32,
         size: f32,
+        fixed: bool,
+        color: Color,
         materials: &mut ResMut<Assets<ColorMaterial>>,
         meshes: &mut ResMut<Assets<Mesh>>,
     ) -> Self {
         Self {
-            ball: Ball::new(position, speed, acceleration, mass, size),
+            ball: Ball::new(position, speed, acceleration, mass, size, fixed),
             mesh: MaterialMesh2dBundle {
                 mesh: Mesh2dHandle(meshes.add(Circle { radius: size })),
-                material: materials.add(Color::linear_rgb(0., 255., 0.)),
+                material: materials.add(color),
                 transform: Transform::from_xyz(position.x * SCALE, position.y * SCALE, 0.0),
                 ..Default::default()
             },
@@ -109,37 +195,262 @@ impl BallBundle {
     }
 }
 
+/// Axis-aligned square region of space, used to recursively split the
+/// universe into quadrants for the Barnes-Hut approximation.
+#[derive(Debug, Clone, Copy)]
+struct Aabb {
+    center: Vec3,
+    half_size: f32,
+}
+
+impl Aabb {
+    /// Index (0..4) of the quadrant of `self` containing `point`.
+    fn quadrant(&self, point: Vec3) -> usize {
+        let right = point.x >= self.center.x;
+        let top = point.y >= self.center.y;
+        match (right, top) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        }
+    }
+
+    fn child(&self, index: usize) -> Aabb {
+        let half = self.half_size / 2.;
+        let offset = match index {
+            0 => Vec3::new(-half, -half, 0.),
+            1 => Vec3::new(half, -half, 0.),
+            2 => Vec3::new(-half, half, 0.),
+            _ => Vec3::new(half, half, 0.),
+        };
+        Aabb {
+            center: self.center + offset,
+            half_size: half,
+        }
+    }
+}
+
+/// Quadtree over ball positions, caching total mass and center-of-mass in
+/// every internal node so the gravitational pull of a whole subtree can be
+/// approximated as a single point mass (Barnes-Hut).
+enum QuadTree {
+    Empty {
+        boundary: Aabb,
+    },
+    Leaf {
+        boundary: Aabb,
+        position: Vec3,
+        mass: f32,
+    },
+    Internal {
+        boundary: Aabb,
+        mass: f32,
+        center_of_mass: Vec3,
+        children: Box<[QuadTree; 4]>,
+    },
+}
+
+impl QuadTree {
+    fn new(boundary: Aabb) -> Self {
+        QuadTree::Empty { boundary }
+    }
+
+    fn insert(&mut self, position: Vec3, mass: f32) {
+        match self {
+            QuadTree::Empty { boundary } => {
+                *self = QuadTree::Leaf {
+                    boundary: *boundary,
+                    position,
+                    mass,
+                };
+            }
+            QuadTree::Leaf {
+                boundary,
+                position: existing_position,
+                mass: existing_mass,
+            } => {
+                let boundary = *boundary;
+                let (existing_position, existing_mass) = (*existing_position, *existing_mass);
+
+                // Coincident (or float-precision-indistinguishable) positions would
+                // land in the same quadrant at every depth and recurse forever; once
+                // the node can no longer usefully separate them, merge into a single
+                // heavier point mass instead of subdividing.
+                if boundary.half_size <= MIN_HALF_SIZE
+                    || existing_position.distance(position) <= f32::EPSILON
+                {
+                    *self = QuadTree::Leaf {
+                        boundary,
+                        position: (existing_position * existing_mass + position * mass)
+                            / (existing_mass + mass),
+                        mass: existing_mass + mass,
+                    };
+                    return;
+                }
+
+                let mut children = Self::empty_children(boundary);
+                Self::insert_into_children(&mut children, boundary, existing_position, existing_mass);
+                Self::insert_into_children(&mut children, boundary, position, mass);
+                *self = QuadTree::Internal {
+                    boundary,
+                    mass: existing_mass + mass,
+                    center_of_mass: (existing_position * existing_mass + position * mass)
+                        / (existing_mass + mass),
+                    children: Box::new(children),
+                };
+            }
+            QuadTree::Internal {
+                boundary,
+                mass: total_mass,
+                center_of_mass,
+                children,
+            } => {
+                *center_of_mass = (*center_of_mass * *total_mass + position * mass)
+                    / (*total_mass + mass);
+                *total_mass += mass;
+                Self::insert_into_children(children, *boundary, position, mass);
+            }
+        }
+    }
+
+    fn empty_children(boundary: Aabb) -> [QuadTree; 4] {
+        [
+            QuadTree::new(boundary.child(0)),
+            QuadTree::new(boundary.child(1)),
+            QuadTree::new(boundary.child(2)),
+            QuadTree::new(boundary.child(3)),
+        ]
+    }
+
+    fn insert_into_children(children: &mut [QuadTree; 4], parent: Aabb, position: Vec3, mass: f32) {
+        let index = parent.quadrant(position);
+        children[index].insert(position, mass);
+    }
+
+    /// Builds a quadtree spanning the bounding box of `balls`.
+    fn build(balls: &[Ball]) -> QuadTree {
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for ball in balls {
+            min = min.min(ball.position);
+            max = max.max(ball.position);
+        }
+        let center = (min + max) / 2.;
+        let half_size = ((max.x - min.x).max(max.y - min.y) / 2.).max(1.);
+
+        let mut tree = QuadTree::new(Aabb { center, half_size });
+        for ball in balls {
+            tree.insert(ball.position, ball.mass);
+        }
+        tree
+    }
+
+    /// Approximates the gravitational acceleration this (sub)tree exerts on
+    /// `ball`, recursing into children until a node is either a leaf or far
+    /// enough away (`node_width / distance < THETA`) to be treated as a
+    /// single point mass at its center of mass.
+    fn acceleration_on(&self, ball: &Ball) -> Vec3 {
+        match self {
+            QuadTree::Empty { .. } => Vec3::ZERO,
+            QuadTree::Leaf { position, mass, .. } => {
+                // `insert` merges coincident balls into a single leaf whose
+                // position is a float average, so it isn't guaranteed to
+                // bitwise-equal the original ball's position even when this
+                // leaf *is* (only) that ball; compare within an epsilon
+                // instead, or a near-zero distance would self-attract the
+                // ball toward a leaf that includes its own mass.
+                if position.distance(ball.position) <= f32::EPSILON {
+                    return Vec3::ZERO;
+                }
+                gravitational_acceleration(ball.position, *position, *mass)
+            }
+            QuadTree::Internal {
+                boundary,
+                mass,
+                center_of_mass,
+                children,
+            } => {
+                // Node width and distance must share units for the opening-angle
+                // ratio to mean what THETA says; `gravitational_acceleration` below
+                // does its own /SCALE for the force law, independently of this test.
+                let distance = ball.position.distance(*center_of_mass);
+                if boundary.half_size * 2. / distance < THETA {
+                    gravitational_acceleration(ball.position, *center_of_mass, *mass)
+                } else {
+                    children
+                        .iter()
+                        .fold(Vec3::ZERO, |acc, child| acc + child.acceleration_on(ball))
+                }
+            }
+        }
+    }
+}
+
+/// Acceleration imparted on a unit mass at `position` by a point mass
+/// `other_mass` located at `other_position`: a plain (unsoftened)
+/// inverse-square law in the `SCALE`-divided distance.
+fn gravitational_acceleration(position: Vec3, other_position: Vec3, other_mass: f32) -> Vec3 {
+    let distance = position.distance(other_position) / SCALE;
+    let normal = (other_position - position).normalize();
+    normal * (other_mass / distance.powi(2))
+}
+
+/// Boids-style flocking acceleration for `ball` given the rest of the
+/// population: separation from close neighbors, alignment with the average
+/// speed of perceived neighbors, and cohesion toward their center of mass.
+fn flocking_acceleration(ball: &Ball, others: &[Ball]) -> Vec3 {
+    let mut separation = Vec3::ZERO;
+    let mut speed_sum = Vec3::ZERO;
+    let mut position_sum = Vec3::ZERO;
+    let mut neighbors = 0;
+
+    for other in others {
+        if other.position == ball.position {
+            continue;
+        }
+        let distance = ball.position.distance(other.position);
+
+        if distance < R_SEP {
+            separation += (ball.position - other.position) / distance.powi(2);
+        }
+
+        if distance < R {
+            speed_sum += other.speed;
+            position_sum += other.position;
+            neighbors += 1;
+        }
+    }
+
+    if neighbors == 0 {
+        return separation * SEP_WEIGHT;
+    }
+
+    let alignment = speed_sum / neighbors as f32 - ball.speed;
+    let cohesion = position_sum / neighbors as f32 - ball.position;
+
+    separation * SEP_WEIGHT + alignment * ALIGN_WEIGHT + cohesion * COH_WEIGHT
+}
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<SimRng>,
+    asset_server: Res<AssetServer>,
+    mut scene_state: ResMut<scene::SceneState>,
 ) {
     commands.spawn(Camera2dBundle::default());
 
-    for _ in 0..10 {
-        let random_position = Vec3::new(
-            (random::<f32>() - 0.5) * UNIVERSE_WIDTH,
-            (random::<f32>() - 0.5) * UNIVERSE_HEIGHT,
-            0.,
-        );
-
-        let random_speed = Vec3::new(
-            (random::<f32>() - 0.5) * 1.,
-            (random::<f32>() - 0.5) * 1.,
-            0.,
-        );
-
-        let mass = (random::<f32>()) * 1.5 + 0.5;
-
-        commands.spawn(BallBundle::new(
-            random_position,
-            random_speed,
-            Vec3::ZERO,
-            mass,
-            20.,
-            &mut materials,
-            &mut meshes,
-        ));
+    match scene::scene_path_from_env() {
+        Some(path) => {
+            scene_state.handle = Some(asset_server.load(path.as_str()));
+            scene_state.path = Some(path);
+        }
+        None => {
+            scene::spawn_random_balls(&mut commands, &mut rng, &mut materials, &mut meshes);
+            scene_state.applied = true;
+        }
     }
 }
 
@@ -147,8 +458,29 @@ fn time_progress(
     keys: Res<ButtonInput<KeyCode>>,
     mut game_state: ResMut<GameState>,
     mut time_progress: ResMut<TimeProgress>,
+    mut sim_mode: ResMut<SimMode>,
+    mut scene_state: ResMut<scene::SceneState>,
+    mut rng: ResMut<SimRng>,
+    asset_server: Res<AssetServer>,
     time: Res<Time>,
 ) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        *sim_mode = sim_mode.next();
+        println!("Sim mode: {:?}", *sim_mode);
+    }
+
+    if keys.just_pressed(KeyCode::KeyR) {
+        println!("Reloading scene");
+        match &scene_state.path {
+            Some(path) => asset_server.reload(path.as_str()),
+            // No scene file to reload from: reseed so the random-spawn
+            // fallback reproduces the original layout instead of drawing
+            // further values from the already-advanced RNG stream.
+            None => *rng = SimRng::default(),
+        }
+        scene_state.applied = false;
+    }
+
     if keys.just_pressed(KeyCode::Space) {
         match *game_state {
             GameState::Running => {
@@ -196,9 +528,11 @@ fn time_progress(
     }
 }
 
-fn update_balls(
+pub(crate) fn update_balls(
     mut commands: Commands,
-    time: Res<Time>,
+    time: Res<Time<Fixed>>,
+    sim_mode: Res<SimMode>,
+    physics: Res<scene::PhysicsParams>,
     mut balls: Query<(Entity, &mut Ball, &mut Transform)>,
 ) {
     let other_balls = balls
@@ -206,6 +540,8 @@ fn update_balls(
         .map(|(_, ball, _)| ball.clone())
         .collect::<Vec<_>>();
 
+    let quad_tree = QuadTree::build(&other_balls);
+
     for (entity_id, mut ball, mut transform) in &mut balls {
         // dbg!(&ball.position);
         // dbg!(&ball.speed);
@@ -217,29 +553,30 @@ fn update_balls(
         }
 
         // If ball is too slow and too low, stop it
-        if ball.speed.norm() < 1. && ball.position.y - ball.size / 2. < -WINDOW_HEIGHT / 2. + 1.0 {
+        if ball.speed.norm() < 1.
+            && ball.position.y - ball.size / 2. < -physics.window_height / 2. + 1.0
+        {
             ball.fixed = true;
             ball.speed = Vec3::ZERO;
         }
 
         let mut acceleration = ball.acceleration;
-        // Forces
-        let weight = Vec3::new(0., G, 0.) * ball.mass;
 
-        let friction = ball.speed * -1. * FRICTION;
+        if matches!(*sim_mode, SimMode::Gravity | SimMode::Both) {
+            // Forces
+            let weight = Vec3::new(0., physics.g, 0.) * ball.mass;
 
-        acceleration += weight;
-        acceleration += friction;
+            let friction = ball.speed * -1. * physics.friction;
 
-        // Attraction
-        for other_ball in other_balls.iter() {
-            if ball.position == other_ball.position {
-                continue;
-            }
-            let distance = ball.position.distance(other_ball.position) / SCALE;
-            let normal = (other_ball.position - ball.position).normalize();
-            let force = normal * (ball.mass * other_ball.mass / distance.powi(2));
-            acceleration += force / ball.mass;
+            acceleration += weight;
+            acceleration += friction;
+
+            // Attraction (Barnes-Hut approximation)
+            acceleration += quad_tree.acceleration_on(&ball);
+        }
+
+        if matches!(*sim_mode, SimMode::Flocking | SimMode::Both) {
+            acceleration += flocking_acceleration(&ball, &other_balls);
         }
 
         ball.speed += acceleration * time.delta_seconds();
@@ -247,34 +584,14 @@ fn update_balls(
         let speed = ball.speed;
         ball.position += speed * time.delta_seconds();
 
-        /*
-        // Balls collision check
-        for other_ball in other_balls.iter() {
-            if ball.position == other_ball.position {
-                continue;
-            }
-            let distance = ball.position.distance(other_ball.position);
-            if distance < ball.size + other_ball.size {
-                let normal = (other_ball.position - ball.position).normalize();
-                let relative_speed = ball.speed - other_ball.speed;
-                let impulse =
-                    2. * relative_speed.dot(normal) / (ball.mass + other_ball.mass) * normal;
-                ball.speed -= impulse * other_ball.mass;
-
-                let size = ball.size;
-                ball.position -= normal * (size + other_ball.size - distance) / 2.;
-            }
-        }
-        */
-
         // Update transform
         transform.translation = ball.position * SCALE;
 
         // If outside off the universe, destroy the ball
-        if transform.translation.x - ball.size / 2. > UNIVERSE_WIDTH / 2.
-            || transform.translation.x + ball.size / 2. < -UNIVERSE_WIDTH / 2.
-            || transform.translation.y - ball.size / 2. > UNIVERSE_HEIGHT / 2.
-            || transform.translation.y + ball.size / 2. < -UNIVERSE_HEIGHT / 2.
+        if transform.translation.x - ball.size / 2. > physics.universe_width / 2.
+            || transform.translation.x + ball.size / 2. < -physics.universe_width / 2.
+            || transform.translation.y - ball.size / 2. > physics.universe_height / 2.
+            || transform.translation.y + ball.size / 2. < -physics.universe_height / 2.
         {
             //dbg!("Despawn {:?}", entity_id);
             commands.entity(entity_id).despawn();
@@ -282,24 +599,227 @@ fn update_balls(
         }
 
         // Bounding off the walls check (last)
-        if transform.translation.x - ball.size / 2. < -WINDOW_WIDTH / 2. && ball.speed.x < 0. {
+        if transform.translation.x - ball.size / 2. < -physics.window_width / 2.
+            && ball.speed.x < 0.
+        {
             ball.speed.x = -ball.speed.x;
-            ball.position.x = -WINDOW_WIDTH / 2. + ball.size / 2.;
-        } else if transform.translation.x + ball.size / 2. > WINDOW_WIDTH / 2. && ball.speed.x > 0.
+            ball.position.x = -physics.window_width / 2. + ball.size / 2.;
+        } else if transform.translation.x + ball.size / 2. > physics.window_width / 2.
+            && ball.speed.x > 0.
         {
             ball.speed.x = -ball.speed.x;
-            ball.position.x = WINDOW_WIDTH / 2. - ball.size / 2.;
+            ball.position.x = physics.window_width / 2. - ball.size / 2.;
         }
-        if transform.translation.y - ball.size / 2. < -WINDOW_HEIGHT / 2. && ball.speed.y < 0. {
+        if transform.translation.y - ball.size / 2. < -physics.window_height / 2.
+            && ball.speed.y < 0.
+        {
             ball.speed.y = -ball.speed.y;
-            ball.position.y = -WINDOW_HEIGHT / 2. + ball.size / 2.;
-        } else if transform.translation.y + ball.size / 2. > WINDOW_HEIGHT / 2. && ball.speed.y > 0.
+            ball.position.y = -physics.window_height / 2. + ball.size / 2.;
+        } else if transform.translation.y + ball.size / 2. > physics.window_height / 2.
+            && ball.speed.y > 0.
         {
             ball.speed.y = -ball.speed.y;
-            ball.position.y = WINDOW_HEIGHT / 2. - ball.size / 2.;
+            ball.position.y = physics.window_height / 2. - ball.size / 2.;
         }
 
         // Update transform
         transform.translation = ball.position * SCALE;
     }
 }
+
+/// Pairwise elastic collision resolution, run after integration so balls
+/// bounce off each other using the positions/speeds `update_balls` just
+/// produced. `iter_combinations_mut` lets us borrow two distinct `Ball`s at
+/// once, which a plain `Query` can't do.
+pub(crate) fn resolve_collisions(mut balls: Query<(&mut Ball, &mut Transform)>) {
+    let mut pairs = balls.iter_combinations_mut();
+    while let Some([(mut a, mut a_transform), (mut b, mut b_transform)]) = pairs.fetch_next() {
+        let distance = a.position.distance(b.position);
+        if distance >= a.size + b.size {
+            continue;
+        }
+
+        // Coincident balls have no well-defined normal; normalizing the zero
+        // vector would produce NaN that then poisons speed/position forever.
+        if distance <= f32::EPSILON {
+            continue;
+        }
+
+        let normal = (b.position - a.position).normalize();
+        let relative_velocity = (a.speed - b.speed).dot(normal);
+        if relative_velocity <= 0. {
+            continue;
+        }
+
+        let inverse_mass_a = if a.fixed { 0. } else { 1. / a.mass };
+        let inverse_mass_b = if b.fixed { 0. } else { 1. / b.mass };
+        let total_inverse_mass = inverse_mass_a + inverse_mass_b;
+        if total_inverse_mass == 0. {
+            continue;
+        }
+
+        let impulse = -(1. + RESTITUTION) * relative_velocity / total_inverse_mass;
+        a.speed += (impulse * inverse_mass_a) * normal;
+        b.speed -= (impulse * inverse_mass_b) * normal;
+
+        // Resolve interpenetration, splitting the overlap by inverse mass.
+        let overlap = a.size + b.size - distance;
+        let correction = normal * (overlap / total_inverse_mass);
+        a.position -= correction * inverse_mass_a;
+        b.position += correction * inverse_mass_b;
+
+        a_transform.translation = a.position * SCALE;
+        b_transform.translation = b.position * SCALE;
+    }
+}
+
+#[cfg(test)]
+mod quad_tree_tests {
+    use super::*;
+
+    #[test]
+    fn insert_coincident_points_merges_instead_of_recursing_forever() {
+        let mut tree = QuadTree::new(Aabb {
+            center: Vec3::ZERO,
+            half_size: 100.,
+        });
+
+        // Same position twice: a pure `Aabb::quadrant` split would put both
+        // in the same child at every depth and recurse forever.
+        tree.insert(Vec3::new(10., 10., 0.), 2.);
+        tree.insert(Vec3::new(10., 10., 0.), 3.);
+
+        match tree {
+            QuadTree::Leaf { position, mass, .. } => {
+                assert_eq!(mass, 5.);
+                assert_eq!(position, Vec3::new(10., 10., 0.));
+            }
+            _ => panic!("coincident points should merge into a single leaf, not subdivide"),
+        }
+    }
+
+    #[test]
+    fn insert_below_min_half_size_merges_instead_of_recursing_forever() {
+        let mut tree = QuadTree::new(Aabb {
+            center: Vec3::ZERO,
+            half_size: MIN_HALF_SIZE,
+        });
+
+        // Distinct but arbitrarily close positions: once a node can no
+        // longer usefully separate them, it should merge rather than keep
+        // shrinking children below `MIN_HALF_SIZE`.
+        tree.insert(Vec3::new(-0.0001, 0., 0.), 1.);
+        tree.insert(Vec3::new(0.0001, 0., 0.), 1.);
+
+        assert!(matches!(tree, QuadTree::Leaf { mass, .. } if mass == 2.));
+    }
+
+    #[test]
+    fn acceleration_on_merged_leaf_does_not_self_attract() {
+        let mut tree = QuadTree::new(Aabb {
+            center: Vec3::ZERO,
+            half_size: 100.,
+        });
+        tree.insert(Vec3::new(5., 0., 0.), 1.);
+        tree.insert(Vec3::new(5., 0., 0.), 1.);
+
+        let ball = Ball::new(Vec3::new(5., 0., 0.), Vec3::ZERO, Vec3::ZERO, 1., 10., false);
+        let acceleration = tree.acceleration_on(&ball);
+
+        assert_eq!(acceleration, Vec3::ZERO);
+    }
+
+    #[test]
+    fn acceleration_on_compares_half_size_against_unscaled_distance() {
+        // `half_size` is in raw meters, never divided by SCALE, so the
+        // opening-angle test must compare it against an equally-unscaled
+        // distance. Pick a node/ball pair whose ratio is a known multiple of
+        // THETA and check the approximation only kicks in on the expected
+        // side of that ratio, not at the `/SCALE`-shifted one.
+        let children = QuadTree::empty_children(Aabb {
+            center: Vec3::ZERO,
+            half_size: 1.,
+        });
+        let node = QuadTree::Internal {
+            boundary: Aabb {
+                center: Vec3::ZERO,
+                half_size: 1.,
+            },
+            mass: 10.,
+            center_of_mass: Vec3::ZERO,
+            children: Box::new(children),
+        };
+
+        // distance such that half_size * 2. / distance is just above THETA:
+        // the node must recurse (children are empty, so acceleration is 0).
+        let just_too_close = Ball::new(
+            Vec3::new(2. / THETA - 0.1, 0., 0.),
+            Vec3::ZERO,
+            Vec3::ZERO,
+            1.,
+            10.,
+            false,
+        );
+        assert_eq!(node.acceleration_on(&just_too_close), Vec3::ZERO);
+
+        // distance such that half_size * 2. / distance is just below THETA:
+        // the node is far enough to approximate as its center of mass.
+        let far_enough = Ball::new(
+            Vec3::new(2. / THETA + 0.1, 0., 0.),
+            Vec3::ZERO,
+            Vec3::ZERO,
+            1.,
+            10.,
+            false,
+        );
+        let expected = gravitational_acceleration(far_enough.position, Vec3::ZERO, 10.);
+        assert_eq!(node.acceleration_on(&far_enough), expected);
+    }
+}
+
+#[cfg(test)]
+mod collision_tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn spawn_ball(world: &mut World, position: Vec3, speed: Vec3, mass: f32, size: f32) -> Entity {
+        world
+            .spawn((
+                Ball::new(position, speed, Vec3::ZERO, mass, size, false),
+                Transform::from_translation(position * SCALE),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn coincident_balls_are_skipped_instead_of_producing_nan() {
+        let mut world = World::new();
+        let a = spawn_ball(&mut world, Vec3::ZERO, Vec3::new(1., 0., 0.), 1., 5.);
+        let b = spawn_ball(&mut world, Vec3::ZERO, Vec3::new(-1., 0., 0.), 1., 5.);
+
+        world.run_system_once(resolve_collisions);
+
+        let ball_a = world.get::<Ball>(a).unwrap();
+        let ball_b = world.get::<Ball>(b).unwrap();
+        // Unresolved (not skipped), a zero-distance normalize would have
+        // produced NaN here, which then never compares equal to anything.
+        assert!(ball_a.speed.is_finite());
+        assert!(ball_b.speed.is_finite());
+        assert_eq!(ball_a.speed, Vec3::new(1., 0., 0.));
+        assert_eq!(ball_b.speed, Vec3::new(-1., 0., 0.));
+    }
+
+    #[test]
+    fn overlapping_balls_bounce_apart_along_the_collision_normal() {
+        let mut world = World::new();
+        let a = spawn_ball(&mut world, Vec3::new(-1., 0., 0.), Vec3::new(1., 0., 0.), 1., 5.);
+        let b = spawn_ball(&mut world, Vec3::new(1., 0., 0.), Vec3::new(-1., 0., 0.), 1., 5.);
+
+        world.run_system_once(resolve_collisions);
+
+        let ball_a = world.get::<Ball>(a).unwrap();
+        let ball_b = world.get::<Ball>(b).unwrap();
+        assert!(ball_a.speed.x < 0.);
+        assert!(ball_b.speed.x > 0.);
+    }
+}
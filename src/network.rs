@@ -0,0 +1,190 @@
+//! Optional GGRS rollback-netcode session, layered on top of the
+//! deterministic fixed-step physics in `main.rs`. Two peers each run the
+//! same `update_balls`/`resolve_collisions` pair inside the GGRS rollback
+//! schedule and stay in sync by exchanging inputs instead of state.
+
+use bevy::prelude::*;
+use bevy_ggrs::{
+    ggrs, AddRollbackCommandExtension, GgrsApp, GgrsPlugin, GgrsSchedule, LocalInputs,
+    LocalPlayers, PlayerInputs, ReadInputs,
+};
+use bytemuck::{Pod, Zeroable};
+use ggrs::{PlayerType, SessionBuilder, UdpNonBlockingSocket};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use crate::{resolve_collisions, update_balls, Ball, BallBundle, GameState};
+
+const INPUT_STEP: u8 = 1 << 0;
+const INPUT_FRAME_FORWARD: u8 = 1 << 1;
+const INPUT_SPAWN: u8 = 1 << 2;
+
+/// Per-frame player input: the same spacebar/step/frame-forward controls
+/// `time_progress` already reads locally, plus "spawn ball at cursor",
+/// packed into the fixed-size struct GGRS serializes over the wire.
+#[derive(Copy, Clone, PartialEq, Eq, Pod, Zeroable, Default, Debug)]
+#[repr(C)]
+pub(crate) struct BallInput {
+    buttons: u8,
+}
+
+/// GGRS config tying our input type to a plain UDP socket address.
+pub(crate) struct GgrsConfig;
+
+impl ggrs::Config for GgrsConfig {
+    type Input = BallInput;
+    type State = u8;
+    type Address = SocketAddr;
+}
+
+/// `--local-port` / `--players` as parsed off the command line. `None` from
+/// [`NetArgs::from_env`] means the caller didn't ask for a networked
+/// session, so `main` keeps running the single-process demo.
+#[derive(Resource, Clone, Debug)]
+pub(crate) struct NetArgs {
+    local_port: u16,
+    players: Vec<String>,
+}
+
+impl NetArgs {
+    pub(crate) fn from_env() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let local_port = flag(&args, "--local-port")?.parse().ok()?;
+        let players = flag(&args, "--players")?
+            .split(',')
+            .map(str::to_owned)
+            .collect();
+        Some(NetArgs {
+            local_port,
+            players,
+        })
+    }
+}
+
+fn flag(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+/// Starts the P2P session described by `net_args`: one local player, the
+/// rest resolved as remote peers by socket address, e.g.
+/// `--players local,127.0.0.1:7001`.
+fn start_session(net_args: &NetArgs) -> ggrs::P2PSession<GgrsConfig> {
+    let mut builder = SessionBuilder::<GgrsConfig>::new()
+        .with_num_players(net_args.players.len());
+
+    for (handle, player) in net_args.players.iter().enumerate() {
+        let player_type = if player == "local" {
+            PlayerType::Local
+        } else {
+            PlayerType::Remote(player.parse().expect("player address must be `ip:port`"))
+        };
+        builder = builder
+            .add_player(player_type, handle)
+            .expect("valid player handle");
+    }
+
+    let socket = UdpNonBlockingSocket::bind_to_port(net_args.local_port)
+        .expect("failed to bind local UDP socket");
+
+    builder
+        .start_p2p_session(socket)
+        .expect("failed to start GGRS P2P session")
+}
+
+/// Wires the rollback schedule into `app`: registers `Ball` (position,
+/// speed, acceleration, mass, size, fixed) and its `Transform` for
+/// snapshot/restore, runs the same physics pair GGRS advances on rollback,
+/// and inserts the started session as a resource.
+pub(crate) fn plugin(app: &mut App, net_args: NetArgs) {
+    app.add_plugins(GgrsPlugin::<GgrsConfig>::default())
+        .set_rollback_schedule_fps(60)
+        .rollback_component_with_clone::<Ball>()
+        .rollback_component_with_clone::<Transform>()
+        .add_systems(ReadInputs, read_local_input)
+        .add_systems(GgrsSchedule, apply_network_input)
+        .add_systems(
+            GgrsSchedule,
+            (update_balls, resolve_collisions)
+                .chain()
+                .after(apply_network_input)
+                // Same gate the local path applies in `main`: without it,
+                // networked physics free-runs regardless of pause/step state.
+                .run_if(resource_equals(GameState::Running)),
+        )
+        .insert_resource(start_session(&net_args))
+        .insert_resource(net_args);
+}
+
+/// Drives `GameState` and ball spawning from the synchronized inputs GGRS
+/// hands back each rollback tick, so every peer reaches the same
+/// state from the same inputs instead of from each peer's own local keys.
+/// Runs before `update_balls`/`resolve_collisions`, which are gated on the
+/// `GameState` this sets.
+///
+/// Cursor position isn't part of `BallInput` (it isn't synchronized between
+/// peers), so "spawn ball at cursor" becomes "spawn ball at a fixed offset
+/// per player handle" here instead.
+fn apply_network_input(
+    mut commands: Commands,
+    inputs: Res<PlayerInputs<GgrsConfig>>,
+    mut game_state: ResMut<GameState>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let running = inputs
+        .iter()
+        .any(|(input, _)| input.buttons & (INPUT_STEP | INPUT_FRAME_FORWARD) != 0);
+    *game_state = if running {
+        GameState::Running
+    } else {
+        GameState::Paused
+    };
+
+    for (handle, (input, _)) in inputs.iter().enumerate() {
+        if input.buttons & INPUT_SPAWN == 0 {
+            continue;
+        }
+        let position = Vec3::new(handle as f32 * 20. - 10., 0., 0.);
+        commands
+            .spawn(BallBundle::new(
+                position,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                1.,
+                10.,
+                false,
+                Color::linear_rgb(0., 255., 0.),
+                &mut materials,
+                &mut meshes,
+            ))
+            .add_rollback();
+    }
+}
+
+/// Reads this frame's local controls into the `BallInput` GGRS ships to
+/// peers and replays on rollback, one input per locally-owned player handle.
+pub(crate) fn read_local_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    local_players: Res<LocalPlayers>,
+) {
+    let mut buttons = 0;
+    if keys.pressed(KeyCode::Space) {
+        buttons |= INPUT_STEP;
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        buttons |= INPUT_FRAME_FORWARD;
+    }
+    if keys.just_pressed(KeyCode::KeyN) {
+        buttons |= INPUT_SPAWN;
+    }
+
+    let mut local_inputs = HashMap::new();
+    for handle in &local_players.0 {
+        local_inputs.insert(*handle, BallInput { buttons });
+    }
+    commands.insert_resource(LocalInputs::<GgrsConfig>(local_inputs));
+}
@@ -0,0 +1,260 @@
+//! RON `Universe` scene format: lets a run start from an explicit,
+//! reproducible layout (universe/window bounds, `G`/`FRICTION`, and a ball
+//! list) instead of the random spawn in [`spawn_random_balls`]. Scenes load
+//! through Bevy's asset system, so [`apply_scene`] just waits for the
+//! handle to resolve before populating the world.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use bevy_ggrs::AddRollbackCommandExtension;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    Ball, BallBundle, SimRng, FRICTION, G, UNIVERSE_HEIGHT, UNIVERSE_WIDTH, WINDOW_HEIGHT,
+    WINDOW_WIDTH,
+};
+
+/// A fully-specified starting layout, deserialized from a `.universe.ron`
+/// file.
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub(crate) struct Universe {
+    universe_width: f32,
+    universe_height: f32,
+    window_width: f32,
+    window_height: f32,
+    g: f32,
+    friction: f32,
+    balls: Vec<BallSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BallSpec {
+    position: Vec3,
+    #[serde(default)]
+    speed: Vec3,
+    mass: f32,
+    size: f32,
+    #[serde(default)]
+    fixed: bool,
+    color: [f32; 3],
+}
+
+#[derive(Debug)]
+pub(crate) enum UniverseLoaderError {
+    Io(std::io::Error),
+    Ron(ron::de::SpannedError),
+}
+
+impl std::fmt::Display for UniverseLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniverseLoaderError::Io(err) => write!(f, "failed to read scene file: {err}"),
+            UniverseLoaderError::Ron(err) => write!(f, "failed to parse scene file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UniverseLoaderError {}
+
+impl From<std::io::Error> for UniverseLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        UniverseLoaderError::Io(err)
+    }
+}
+
+impl From<ron::de::SpannedError> for UniverseLoaderError {
+    fn from(err: ron::de::SpannedError) -> Self {
+        UniverseLoaderError::Ron(err)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct UniverseLoader;
+
+impl AssetLoader for UniverseLoader {
+    type Asset = Universe;
+    type Settings = ();
+    type Error = UniverseLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Universe, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["universe.ron"]
+    }
+}
+
+/// Scene-tunable physics/bounds parameters, defaulting to the hardcoded
+/// constants and overwritten once a `Universe` scene finishes loading.
+#[derive(Resource, Debug, Clone, Copy)]
+pub(crate) struct PhysicsParams {
+    pub(crate) universe_width: f32,
+    pub(crate) universe_height: f32,
+    pub(crate) window_width: f32,
+    pub(crate) window_height: f32,
+    pub(crate) g: f32,
+    pub(crate) friction: f32,
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        Self {
+            universe_width: UNIVERSE_WIDTH,
+            universe_height: UNIVERSE_HEIGHT,
+            window_width: WINDOW_WIDTH,
+            window_height: WINDOW_HEIGHT,
+            g: G,
+            friction: FRICTION,
+        }
+    }
+}
+
+/// `--scene <path>` as parsed off the command line, if given.
+pub(crate) fn scene_path_from_env() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--scene")
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+/// Which scene (if any) is being loaded, and whether it has been applied to
+/// the world yet. Set `applied` back to `false` to trigger a reload.
+#[derive(Resource, Default)]
+pub(crate) struct SceneState {
+    pub(crate) path: Option<String>,
+    pub(crate) handle: Option<Handle<Universe>>,
+    pub(crate) applied: bool,
+}
+
+/// Respawns the random 10-ball cloud used when no scene is requested.
+pub(crate) fn spawn_random_balls(
+    commands: &mut Commands,
+    rng: &mut SimRng,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) {
+    for _ in 0..10 {
+        let random_position = Vec3::new(
+            (rng.gen::<f32>() - 0.5) * UNIVERSE_WIDTH,
+            (rng.gen::<f32>() - 0.5) * UNIVERSE_HEIGHT,
+            0.,
+        );
+
+        let random_speed = Vec3::new(
+            (rng.gen::<f32>() - 0.5) * 1.,
+            (rng.gen::<f32>() - 0.5) * 1.,
+            0.,
+        );
+
+        let mass = rng.gen::<f32>() * 1.5 + 0.5;
+
+        commands
+            .spawn(BallBundle::new(
+                random_position,
+                random_speed,
+                Vec3::ZERO,
+                mass,
+                20.,
+                false,
+                Color::linear_rgb(0., 255., 0.),
+                materials,
+                meshes,
+            ))
+            .add_rollback();
+    }
+}
+
+/// Despawns every ball and spawns `universe.balls` in its place, then
+/// overwrites `physics` with the scene's parameters.
+fn apply_universe(
+    commands: &mut Commands,
+    existing_balls: &Query<Entity, With<Ball>>,
+    universe: &Universe,
+    physics: &mut PhysicsParams,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) {
+    for entity in existing_balls.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for ball in &universe.balls {
+        let color = Color::srgb(ball.color[0], ball.color[1], ball.color[2]);
+        commands
+            .spawn(BallBundle::new(
+                ball.position,
+                ball.speed,
+                Vec3::ZERO,
+                ball.mass,
+                ball.size,
+                ball.fixed,
+                color,
+                materials,
+                meshes,
+            ))
+            .add_rollback();
+    }
+
+    *physics = PhysicsParams {
+        universe_width: universe.universe_width,
+        universe_height: universe.universe_height,
+        window_width: universe.window_width,
+        window_height: universe.window_height,
+        g: universe.g,
+        friction: universe.friction,
+    };
+}
+
+/// Waits for a requested scene to finish loading (or spawns the random
+/// fallback when none was requested), then applies it once. Runs again on
+/// reload, when [`SceneState::applied`] is reset to `false`.
+pub(crate) fn apply_scene(
+    mut commands: Commands,
+    mut scene_state: ResMut<SceneState>,
+    universes: Res<Assets<Universe>>,
+    existing_balls: Query<Entity, With<Ball>>,
+    mut physics: ResMut<PhysicsParams>,
+    mut rng: ResMut<SimRng>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if scene_state.applied {
+        return;
+    }
+
+    match (&scene_state.path, scene_state.handle.clone()) {
+        (Some(_), Some(handle)) => {
+            let Some(universe) = universes.get(&handle) else {
+                return;
+            };
+            apply_universe(
+                &mut commands,
+                &existing_balls,
+                universe,
+                &mut physics,
+                &mut materials,
+                &mut meshes,
+            );
+        }
+        _ => {
+            for entity in existing_balls.iter() {
+                commands.entity(entity).despawn();
+            }
+            spawn_random_balls(&mut commands, &mut rng, &mut materials, &mut meshes);
+        }
+    }
+
+    scene_state.applied = true;
+}